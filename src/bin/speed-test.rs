@@ -1,5 +1,8 @@
 use indicatif::{ProgressBar, ProgressStyle};
-use speed_test::{SpeedTest, SpeedTestEvents, SpeedTestResult, TargetDownloadInformation};
+use speed_test::{
+    LatencyInformation, SpeedTest, SpeedTestEvents, SpeedTestResult, TargetDownloadInformation,
+    TargetUploadInformation,
+};
 
 #[derive(Debug)]
 struct SpeedTestProgressBar {
@@ -19,6 +22,7 @@ impl SpeedTestProgressBar {
 
 impl SpeedTestEvents for SpeedTestProgressBar {
     fn on_download(&mut self, target_download_information: &TargetDownloadInformation) {
+        self.progress_bar.set_message("Downloading");
         self.progress_bar
             .set_length(target_download_information.total_bytes);
     }
@@ -31,6 +35,29 @@ impl SpeedTestEvents for SpeedTestProgressBar {
     fn on_downloaded(&mut self, _target_download_information: &TargetDownloadInformation) {
         self.progress_bar.finish_with_message("Finished");
     }
+
+    fn on_upload(&mut self, target_upload_information: &TargetUploadInformation) {
+        self.progress_bar.reset();
+        self.progress_bar.set_message("Uploading");
+        self.progress_bar
+            .set_length(target_upload_information.total_bytes);
+    }
+
+    fn on_uploading(&mut self, target_upload_information: &TargetUploadInformation) {
+        self.progress_bar
+            .set_position(target_upload_information.bytes_uploaded);
+    }
+
+    fn on_uploaded(&mut self, _target_upload_information: &TargetUploadInformation) {
+        self.progress_bar.finish_with_message("Finished");
+    }
+
+    fn on_latency(&mut self, latency_information: &LatencyInformation) {
+        self.progress_bar.println(format!(
+            "Latency: {:.1}ms avg, {:.1}ms jitter ({} samples)",
+            latency_information.avg_ms, latency_information.jitter_ms, latency_information.samples
+        ));
+    }
 }
 
 #[tokio::main]
@@ -39,7 +66,9 @@ async fn main() -> SpeedTestResult<()> {
     let speed_test_progress_bar = SpeedTestProgressBar::new();
 
     speed_test.add_events_hook(speed_test_progress_bar);
+    speed_test.measure_latency().await?;
     speed_test.measure_download_speed().await?;
+    speed_test.measure_upload_speed().await?;
 
     Ok(())
 }
@@ -1,34 +1,89 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![warn(missing_debug_implementations, missing_copy_implementations)]
 
-use futures_util::StreamExt;
+use futures_util::{stream, StreamExt};
 use parking_lot::RwLock;
 use reqwest::Client as ReqwestClient;
 use serde::Deserialize;
-use std::{convert::TryFrom, error::Error, fmt, sync::Arc, time::Instant};
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 pub type SpeedTestResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
 
+/// Size, in bytes, of the payload uploaded to each target when no explicit
+/// `upload_size` has been set. Matches the payload size fast.com itself uses
+/// for its upload test.
+const DEFAULT_UPLOAD_SIZE: u64 = 26_214_400;
+
+/// Size of each chunk POSTed during `measure_upload_speed`, so progress can
+/// be reported incrementally instead of only once the whole payload lands.
+const UPLOAD_CHUNK_SIZE: u64 = 1_048_576;
+
+/// Number of round-trip samples `measure_latency` takes against each target.
+const LATENCY_SAMPLES_PER_TARGET: usize = 3;
+
+/// Window over which `current_bps` is computed, so it reflects recent
+/// throughput rather than the average over the whole run.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(2);
+
+/// `reqwest` client tuning for `SpeedTest`. Applies to every request the
+/// crate makes, including the `setup_api` lookup.
+#[derive(Debug, Default, Clone)]
+pub struct SpeedTestConfig {
+    pub request_timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+}
+
 pub trait SpeedTestEvents {
     fn on_download(&mut self, target_download_information: &TargetDownloadInformation);
     fn on_downloading(&mut self, target_download_information: &TargetDownloadInformation);
     fn on_downloaded(&mut self, target_download_information: &TargetDownloadInformation);
+
+    fn on_upload(&mut self, target_upload_information: &TargetUploadInformation);
+    fn on_uploading(&mut self, target_upload_information: &TargetUploadInformation);
+    fn on_uploaded(&mut self, target_upload_information: &TargetUploadInformation);
+
+    fn on_latency(&mut self, latency_information: &LatencyInformation);
 }
 
 pub struct SpeedTest {
     token: String,
     url_count: Option<u64>,
+    upload_size: Option<u64>,
+    concurrency: Option<usize>,
+    max_duration: Option<Duration>,
+    config: SpeedTestConfig,
+    http_client: Option<ReqwestClient>,
     client: Option<Client>,
     targets: Option<Vec<Target>>,
-    hooks: Vec<Arc<RwLock<dyn SpeedTestEvents>>>,
+    hooks: Vec<Arc<RwLock<dyn SpeedTestEvents + Send + Sync>>>,
+    abort_flag: Arc<AtomicBool>,
 }
 
 impl fmt::Debug for SpeedTest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{ token: {}, url_count: {:?}, client: {:?}, targets: {:?} }}",
-            self.token, self.url_count, self.client, self.targets
+            "{{ token: {}, url_count: {:?}, upload_size: {:?}, concurrency: {:?}, max_duration: {:?}, config: {:?}, client: {:?}, targets: {:?} }}",
+            self.token,
+            self.url_count,
+            self.upload_size,
+            self.concurrency,
+            self.max_duration,
+            self.config,
+            self.client,
+            self.targets
         )
     }
 }
@@ -41,17 +96,110 @@ struct Client {
     ip: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct Location {
-    country: String,
-    city: String,
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Location {
+    pub country: String,
+    pub city: String,
+
+    /// fast.com's `v2` API doesn't include coordinates on `client`/`target`
+    /// locations. Left here (rather than dropped) so a future response that
+    /// does carry them is honoured without a schema change; `geocode` is the
+    /// fallback for the common case where they're absent.
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lon: Option<f64>,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct Target {
-    url: String,
-    location: Location,
-    name: String,
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Target {
+    pub url: String,
+    pub location: Location,
+    pub name: String,
+
+    /// Great-circle distance in kilometres from the client to this target,
+    /// filled in by `setup_api` once the client's location is known. `None`
+    /// when either location's coordinates can't be determined, directly or
+    /// via `geocode`.
+    #[serde(skip)]
+    pub distance_km: Option<f64>,
+}
+
+/// Coordinates for fast.com CDN locations that the `v2` API identifies by
+/// country/city but doesn't give coordinates for. Not exhaustive — only
+/// covers commonly observed locations — so `geocode` still falls back to
+/// `None` for anywhere missing.
+const KNOWN_CITY_COORDINATES: &[(&str, &str, f64, f64)] = &[
+    ("US", "New York", 40.7128, -74.0060),
+    ("US", "Los Angeles", 34.0522, -118.2437),
+    ("US", "Chicago", 41.8781, -87.6298),
+    ("US", "Dallas", 32.7767, -96.7970),
+    ("US", "Miami", 25.7617, -80.1918),
+    ("US", "Seattle", 47.6062, -122.3321),
+    ("US", "San Jose", 37.3382, -121.8863),
+    ("US", "Ashburn", 39.0437, -77.4875),
+    ("CA", "Toronto", 43.6532, -79.3832),
+    ("CA", "Montreal", 45.5019, -73.5674),
+    ("CA", "Vancouver", 49.2827, -123.1207),
+    ("MX", "Mexico City", 19.4326, -99.1332),
+    ("BR", "Sao Paulo", -23.5505, -46.6333),
+    ("AR", "Buenos Aires", -34.6037, -58.3816),
+    ("GB", "London", 51.5072, -0.1276),
+    ("FR", "Paris", 48.8566, 2.3522),
+    ("DE", "Frankfurt", 50.1109, 8.6821),
+    ("NL", "Amsterdam", 52.3676, 4.9041),
+    ("ES", "Madrid", 40.4168, -3.7038),
+    ("IT", "Milan", 45.4642, 9.1900),
+    ("SE", "Stockholm", 59.3293, 18.0686),
+    ("PL", "Warsaw", 52.2297, 21.0122),
+    ("AE", "Dubai", 25.2048, 55.2708),
+    ("IN", "Mumbai", 19.0760, 72.8777),
+    ("IN", "Delhi", 28.7041, 77.1025),
+    ("SG", "Singapore", 1.3521, 103.8198),
+    ("JP", "Tokyo", 35.6762, 139.6503),
+    ("KR", "Seoul", 37.5665, 126.9780),
+    ("HK", "Hong Kong", 22.3193, 114.1694),
+    ("AU", "Sydney", -33.8688, 151.2093),
+    ("AU", "Melbourne", -37.8136, 144.9631),
+    ("ZA", "Johannesburg", -26.2041, 28.0473),
+];
+
+/// Looks up a fallback `(lat, lon)` for a location by country/city when the
+/// API response itself didn't include coordinates. Matching is
+/// case-insensitive since `KNOWN_CITY_COORDINATES` is hand-maintained and
+/// fast.com's casing isn't a documented contract.
+fn geocode(location: &Location) -> Option<(f64, f64)> {
+    KNOWN_CITY_COORDINATES
+        .iter()
+        .find(|(country, city, _, _)| {
+            country.eq_ignore_ascii_case(&location.country) && city.eq_ignore_ascii_case(&location.city)
+        })
+        .map(|&(_, _, lat, lon)| (lat, lon))
+}
+
+/// Resolves a location's coordinates, preferring `lat`/`lon` from the API
+/// response itself and falling back to `geocode` by country/city.
+fn resolve_coordinates(location: &Location) -> Option<(f64, f64)> {
+    location.lat.zip(location.lon).or_else(|| geocode(location))
+}
+
+/// Distance in kilometres between two locations, using the haversine
+/// formula on an earth radius of 6371km. Returns `None` if either
+/// location's coordinates can't be resolved, directly or via `geocode`.
+fn haversine_distance_km(from: &Location, to: &Location) -> Option<f64> {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    let (lat1, lon1) = resolve_coordinates(from)?;
+    let (lat2, lon2) = resolve_coordinates(to)?;
+
+    let lat1_rad = lat1.to_radians();
+    let lat2_rad = lat2.to_radians();
+    let dlat = (lat2 - lat1).to_radians();
+    let dlon = (lon2 - lon1).to_radians();
+
+    let a = (dlat / 2.0).sin().powi(2) + lat1_rad.cos() * lat2_rad.cos() * (dlon / 2.0).sin().powi(2);
+
+    Some(EARTH_RADIUS_KM * 2.0 * a.sqrt().atan2((1.0 - a).sqrt()))
 }
 
 #[derive(Debug, Default, Copy, Clone)]
@@ -59,6 +207,28 @@ pub struct TargetDownloadInformation {
     pub bytes_downloaded: u64,
     pub total_bytes: u64,
     pub time_elapsed: u128,
+
+    /// Instantaneous throughput over the last `THROUGHPUT_WINDOW`, in
+    /// bytes/sec, as opposed to `bytes_downloaded`/`time_elapsed`'s
+    /// cumulative average.
+    pub current_bps: u64,
+    pub peak_bps: u64,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TargetUploadInformation {
+    pub bytes_uploaded: u64,
+    pub total_bytes: u64,
+    pub time_elapsed: u128,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LatencyInformation {
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub jitter_ms: f64,
+    pub samples: u32,
 }
 
 impl SpeedTest {
@@ -66,30 +236,112 @@ impl SpeedTest {
         SpeedTest {
             token: token.to_string(),
             url_count: None,
+            upload_size: None,
+            concurrency: None,
+            max_duration: None,
+            config: SpeedTestConfig::default(),
+            http_client: None,
             client: None,
             targets: None,
             hooks: Vec::new(),
+            abort_flag: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    fn get_reqwest_client(&self) -> SpeedTestResult<ReqwestClient> {
-        ReqwestClient::builder().build().map_err(Into::into)
+    /// Sets the `reqwest` client tuning (timeouts, proxy, user agent) used
+    /// for every request the crate makes from this point on.
+    pub fn set_config(&mut self, config: SpeedTestConfig) {
+        self.http_client = None;
+        self.config = config;
+    }
+
+    /// Builds (or returns the already-built) shared `reqwest` client, so
+    /// `setup_api` and the `measure_*` methods reuse the same connection
+    /// pool instead of each opening their own.
+    fn get_reqwest_client(&mut self) -> SpeedTestResult<ReqwestClient> {
+        if let Some(http_client) = &self.http_client {
+            return Ok(http_client.clone());
+        }
+
+        let user_agent = self
+            .config
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| concat!("speed-test-rs/", env!("CARGO_PKG_VERSION")).to_string());
+
+        let mut builder = ReqwestClient::builder().user_agent(user_agent);
+
+        if let Some(request_timeout) = self.config.request_timeout {
+            builder = builder.timeout(request_timeout);
+        }
+
+        if let Some(connect_timeout) = self.config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        if let Some(proxy) = &self.config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        let http_client = builder.build()?;
+        self.http_client = Some(http_client.clone());
+
+        Ok(http_client)
     }
 
-    pub fn add_events_hook<S: SpeedTestEvents + 'static>(&mut self, hook: S) -> Arc<RwLock<S>> {
+    pub fn add_events_hook<S: SpeedTestEvents + Send + Sync + 'static>(
+        &mut self,
+        hook: S,
+    ) -> Arc<RwLock<S>> {
         let hook = Arc::new(RwLock::new(hook));
         self.hooks.push(hook.clone());
         hook
     }
 
+    /// Sets how many targets are requested from the fast.com API.
+    pub fn set_url_count(&mut self, url_count: u64) {
+        self.url_count = Some(url_count);
+    }
+
+    /// Sets the payload size, in bytes, `measure_upload_speed` POSTs to each
+    /// target. Defaults to `DEFAULT_UPLOAD_SIZE`.
+    pub fn set_upload_size(&mut self, upload_size: u64) {
+        self.upload_size = Some(upload_size);
+    }
+
+    /// Sets how many targets `measure_download_speed` downloads from
+    /// concurrently. Defaults to downloading from every target at once.
+    /// Clamped to at least 1, since 0 would never poll the download stream
+    /// and `measure_download_speed` would return with `bytes_downloaded: 0`
+    /// instead of an error.
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = Some(concurrency.max(1));
+    }
+
+    /// Caps how long `measure_download_speed` samples for; once elapsed it
+    /// stops downloading and returns what it has measured so far.
+    pub fn set_max_duration(&mut self, max_duration: Duration) {
+        self.max_duration = Some(max_duration);
+    }
+
+    /// Returns a handle that can be flipped to `true` to stop a running
+    /// `measure_download_speed` early, e.g. from a Ctrl-C handler.
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        self.abort_flag.clone()
+    }
+
     pub async fn measure_download_speed(&mut self) -> SpeedTestResult<TargetDownloadInformation> {
         let client = self.get_reqwest_client()?;
         let mut target_download_information: TargetDownloadInformation = Default::default();
 
         self.setup_api().await?;
 
-        if let Some(targets) = &self.targets {
-            for target in targets {
+        if let Some(targets) = self.targets.clone() {
+            for target in &targets {
+                if self.abort_flag.load(Ordering::Relaxed) {
+                    break;
+                }
+
                 let content_length = client
                     .head(&target.url)
                     .send()
@@ -104,21 +356,128 @@ impl SpeedTest {
             }
 
             let now = Instant::now();
+            let bytes_downloaded = Arc::new(AtomicU64::new(0));
+            let concurrency = self.concurrency.unwrap_or_else(|| targets.len().max(1));
+            let total_bytes = target_download_information.total_bytes;
+            let max_duration = self.max_duration;
+            let abort_flag = self.abort_flag.clone();
+
+            let current_bps = Arc::new(AtomicU64::new(0));
+            let peak_bps = Arc::new(AtomicU64::new(0));
+
+            let progress_hooks = self.hooks.clone();
+            let progress_bytes_downloaded = bytes_downloaded.clone();
+            let progress_abort_flag = abort_flag.clone();
+            let progress_current_bps = current_bps.clone();
+            let progress_peak_bps = peak_bps.clone();
+            let progress_task = tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(100));
+                let mut throughput_samples: VecDeque<(Duration, u64)> = VecDeque::new();
+
+                loop {
+                    interval.tick().await;
+
+                    let elapsed = now.elapsed();
+                    let bytes_downloaded = progress_bytes_downloaded.load(Ordering::Relaxed);
+
+                    throughput_samples.push_back((elapsed, bytes_downloaded));
+                    while throughput_samples
+                        .front()
+                        .is_some_and(|&(sample_elapsed, _)| elapsed - sample_elapsed > THROUGHPUT_WINDOW)
+                    {
+                        throughput_samples.pop_front();
+                    }
 
-            for target in targets {
-                let mut stream = client.get(&target.url).send().await?.bytes_stream();
+                    let window_bps = match (throughput_samples.front(), throughput_samples.back()) {
+                        (Some(&(oldest_elapsed, oldest_bytes)), Some(&(newest_elapsed, newest_bytes)))
+                            if newest_elapsed > oldest_elapsed =>
+                        {
+                            let window_secs = (newest_elapsed - oldest_elapsed).as_secs_f64();
+                            ((newest_bytes - oldest_bytes) as f64 / window_secs) as u64
+                        }
+                        _ => 0,
+                    };
+
+                    progress_current_bps.store(window_bps, Ordering::Relaxed);
+                    progress_peak_bps.fetch_max(window_bps, Ordering::Relaxed);
+
+                    let info = TargetDownloadInformation {
+                        bytes_downloaded,
+                        total_bytes,
+                        time_elapsed: elapsed.as_nanos(),
+                        current_bps: window_bps,
+                        peak_bps: progress_peak_bps.load(Ordering::Relaxed),
+                    };
+
+                    for hook in &progress_hooks {
+                        hook.write().on_downloading(&info);
+                    }
 
-                while let Some(item) = stream.next().await {
-                    target_download_information.bytes_downloaded += u64::try_from(item?.len())?;
-                    target_download_information.time_elapsed = now.elapsed().as_nanos();
+                    let timed_out = max_duration.is_some_and(|max_duration| now.elapsed() > max_duration);
 
-                    for hook in &mut self.hooks {
-                        hook.write().on_downloading(&target_download_information);
+                    if bytes_downloaded >= total_bytes
+                        || progress_abort_flag.load(Ordering::Relaxed)
+                        || timed_out
+                    {
+                        break;
                     }
                 }
+            });
+
+            let results: Vec<SpeedTestResult<()>> = stream::iter(targets)
+                .map(|target| {
+                    let client = client.clone();
+                    let bytes_downloaded = bytes_downloaded.clone();
+                    let abort_flag = abort_flag.clone();
+
+                    async move {
+                        if abort_flag.load(Ordering::Relaxed)
+                            || max_duration.is_some_and(|max_duration| now.elapsed() > max_duration)
+                        {
+                            return Ok(());
+                        }
+
+                        let mut stream = client.get(&target.url).send().await?.bytes_stream();
+                        let mut cancel_check = tokio::time::interval(Duration::from_millis(50));
+                        cancel_check.tick().await;
+
+                        loop {
+                            let is_cancelled = tokio::select! {
+                                item = stream.next() => match item {
+                                    Some(item) => {
+                                        bytes_downloaded.fetch_add(u64::try_from(item?.len())?, Ordering::Relaxed);
+                                        false
+                                    }
+                                    None => break,
+                                },
+                                _ = cancel_check.tick() => {
+                                    abort_flag.load(Ordering::Relaxed)
+                                        || max_duration.is_some_and(|max_duration| now.elapsed() > max_duration)
+                                },
+                            };
+
+                            if is_cancelled {
+                                break;
+                            }
+                        }
+
+                        Ok(())
+                    }
+                })
+                .buffer_unordered(concurrency)
+                .collect()
+                .await;
+
+            progress_task.abort();
+
+            for result in results {
+                result?;
             }
 
+            target_download_information.bytes_downloaded = bytes_downloaded.load(Ordering::Relaxed);
             target_download_information.time_elapsed = now.elapsed().as_nanos();
+            target_download_information.current_bps = current_bps.load(Ordering::Relaxed);
+            target_download_information.peak_bps = peak_bps.load(Ordering::Relaxed);
         }
 
         for hook in &mut self.hooks {
@@ -128,7 +487,101 @@ impl SpeedTest {
         Ok(target_download_information)
     }
 
+    pub async fn measure_upload_speed(&mut self) -> SpeedTestResult<TargetUploadInformation> {
+        let client = self.get_reqwest_client()?;
+        let mut target_upload_information: TargetUploadInformation = Default::default();
+
+        self.setup_api().await?;
+
+        let upload_size = self.upload_size.unwrap_or(DEFAULT_UPLOAD_SIZE);
+
+        if let Some(targets) = &self.targets {
+            target_upload_information.total_bytes = upload_size * targets.len() as u64;
+
+            for hook in &mut self.hooks {
+                hook.write().on_upload(&target_upload_information);
+            }
+
+            let now = Instant::now();
+
+            for target in targets {
+                let mut bytes_remaining = upload_size;
+
+                while bytes_remaining > 0 {
+                    let chunk_size = bytes_remaining.min(UPLOAD_CHUNK_SIZE);
+                    let payload = vec![0u8; usize::try_from(chunk_size)?];
+
+                    client.post(&target.url).body(payload).send().await?;
+
+                    bytes_remaining -= chunk_size;
+                    target_upload_information.bytes_uploaded += chunk_size;
+                    target_upload_information.time_elapsed = now.elapsed().as_nanos();
+
+                    for hook in &mut self.hooks {
+                        hook.write().on_uploading(&target_upload_information);
+                    }
+                }
+            }
+
+            target_upload_information.time_elapsed = now.elapsed().as_nanos();
+        }
+
+        for hook in &mut self.hooks {
+            hook.write().on_uploaded(&target_upload_information);
+        }
+
+        Ok(target_upload_information)
+    }
+
+    pub async fn measure_latency(&mut self) -> SpeedTestResult<LatencyInformation> {
+        let client = self.get_reqwest_client()?;
+        let mut latency_information: LatencyInformation = Default::default();
+
+        self.setup_api().await?;
+
+        if let Some(targets) = self.targets.clone() {
+            let mut rtt_samples_ms = Vec::with_capacity(targets.len() * LATENCY_SAMPLES_PER_TARGET);
+
+            for target in &targets {
+                for _ in 0..LATENCY_SAMPLES_PER_TARGET {
+                    let now = Instant::now();
+                    client.head(&target.url).send().await?;
+                    rtt_samples_ms.push(now.elapsed().as_secs_f64() * 1000.0);
+                }
+            }
+
+            if !rtt_samples_ms.is_empty() {
+                latency_information.min_ms = rtt_samples_ms.iter().cloned().fold(f64::MAX, f64::min);
+                latency_information.max_ms = rtt_samples_ms.iter().cloned().fold(f64::MIN, f64::max);
+                latency_information.avg_ms =
+                    rtt_samples_ms.iter().sum::<f64>() / rtt_samples_ms.len() as f64;
+
+                let jitter_samples = rtt_samples_ms.len() - 1;
+                latency_information.jitter_ms = if jitter_samples > 0 {
+                    let jitter_total: f64 = rtt_samples_ms
+                        .windows(2)
+                        .map(|pair| (pair[1] - pair[0]).abs())
+                        .sum();
+
+                    jitter_total / jitter_samples as f64
+                } else {
+                    0.0
+                };
+
+                latency_information.samples = u32::try_from(rtt_samples_ms.len())?;
+            }
+        }
+
+        for hook in &mut self.hooks {
+            hook.write().on_latency(&latency_information);
+        }
+
+        Ok(latency_information)
+    }
+
     async fn setup_api(&mut self) -> SpeedTestResult<()> {
+        let client = self.get_reqwest_client()?;
+
         let url_count = match self.url_count {
             Some(url_count) => url_count,
             None => 5,
@@ -145,13 +598,34 @@ impl SpeedTest {
             targets: Vec<Target>,
         }
 
-        let response = reqwest::get(&url).await?.json::<Response>().await?;
+        let response = client.get(&url).send().await?.json::<Response>().await?;
+
+        let mut targets = response.targets;
+        for target in &mut targets {
+            target.distance_km = haversine_distance_km(&response.client.location, &target.location);
+        }
+        targets.sort_by(|a, b| match (a.distance_km, b.distance_km) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
 
         self.client = Some(response.client);
-        self.targets = Some(response.targets);
+        self.targets = Some(targets);
 
         Ok(())
     }
+
+    /// Returns the `n` targets closest to the client, ascending by
+    /// great-circle distance. `setup_api` already sorts targets this way, so
+    /// this is a cheap slice once the API response has been fetched.
+    pub fn closest_targets(&self, n: usize) -> Vec<&Target> {
+        self.targets
+            .as_ref()
+            .map(|targets| targets.iter().take(n).collect())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -160,6 +634,11 @@ mod tests {
 
     const TOKEN: &str = "YXNkZmFzZGxmbnNkYWZoYXNkZmhrYWxm";
 
+    /// Keep test uploads tiny — these POST to fast.com's production
+    /// endpoints, not a mock, so there's no reason to push tens of
+    /// megabytes per target on every test run.
+    const TEST_UPLOAD_SIZE: u64 = 16 * 1024;
+
     #[test]
     fn speed_test_new_works() {
         let speed_test = SpeedTest::new(TOKEN);
@@ -168,6 +647,124 @@ mod tests {
         assert_eq!(speed_test.client, None);
         assert_eq!(speed_test.targets, None);
         assert_eq!(speed_test.url_count, None);
+        assert_eq!(speed_test.upload_size, None);
+        assert_eq!(speed_test.concurrency, None);
+        assert_eq!(speed_test.max_duration, None);
+        assert_eq!(speed_test.abort_flag.load(Ordering::Relaxed), false);
+    }
+
+    #[test]
+    fn haversine_distance_km_works() {
+        let new_york = Location {
+            country: "US".to_string(),
+            city: "New York".to_string(),
+            lat: Some(40.7128),
+            lon: Some(-74.0060),
+        };
+        let los_angeles = Location {
+            country: "US".to_string(),
+            city: "Los Angeles".to_string(),
+            lat: Some(34.0522),
+            lon: Some(-118.2437),
+        };
+
+        let distance = haversine_distance_km(&new_york, &los_angeles).unwrap();
+
+        assert!((distance - 3936.0).abs() < 50.0);
+    }
+
+    #[test]
+    fn haversine_distance_km_without_coordinates_is_none() {
+        let new_york = Location {
+            country: "US".to_string(),
+            city: "New York".to_string(),
+            lat: Some(40.7128),
+            lon: Some(-74.0060),
+        };
+        let unknown = Location {
+            country: "US".to_string(),
+            city: "Unknown".to_string(),
+            lat: None,
+            lon: None,
+        };
+
+        assert_eq!(haversine_distance_km(&new_york, &unknown), None);
+    }
+
+    #[test]
+    fn geocode_known_city_works() {
+        let new_york = Location {
+            country: "US".to_string(),
+            city: "New York".to_string(),
+            lat: None,
+            lon: None,
+        };
+
+        assert_eq!(geocode(&new_york), Some((40.7128, -74.0060)));
+    }
+
+    #[test]
+    fn geocode_unknown_city_is_none() {
+        let unknown = Location {
+            country: "US".to_string(),
+            city: "Nowhereville".to_string(),
+            lat: None,
+            lon: None,
+        };
+
+        assert_eq!(geocode(&unknown), None);
+    }
+
+    #[test]
+    fn resolve_coordinates_prefers_explicit_lat_lon_over_geocode() {
+        let new_york_offset = Location {
+            country: "US".to_string(),
+            city: "New York".to_string(),
+            lat: Some(1.0),
+            lon: Some(2.0),
+        };
+
+        assert_eq!(resolve_coordinates(&new_york_offset), Some((1.0, 2.0)));
+    }
+
+    #[test]
+    fn speed_test_set_concurrency_works() {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        speed_test.set_url_count(3);
+        speed_test.set_concurrency(2);
+
+        assert_eq!(speed_test.url_count, Some(3));
+        assert_eq!(speed_test.concurrency, Some(2));
+    }
+
+    #[test]
+    fn speed_test_get_reqwest_client_caches_client() -> SpeedTestResult<()> {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        speed_test.set_config(SpeedTestConfig {
+            user_agent: Some("speed-test-rs-tests".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(speed_test.http_client.is_none(), true);
+
+        speed_test.get_reqwest_client()?;
+
+        assert_eq!(speed_test.http_client.is_some(), true);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn speed_test_cancel_handle_stops_download() -> SpeedTestResult<()> {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        let cancel_handle = speed_test.cancel_handle();
+        cancel_handle.store(true, Ordering::Relaxed);
+
+        let target_download_information = speed_test.measure_download_speed().await?;
+
+        assert_eq!(target_download_information.bytes_downloaded, 0);
+
+        Ok(())
     }
 
     #[tokio::test]
@@ -181,6 +778,38 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn speed_test_closest_targets_works() -> SpeedTestResult<()> {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        speed_test.setup_api().await?;
+
+        let closest = speed_test.closest_targets(2);
+
+        assert!(closest.len() <= 2);
+        assert!(closest.windows(2).all(|pair| match (pair[0].distance_km, pair[1].distance_km) {
+            (Some(a), Some(b)) => a <= b,
+            (Some(_), None) => true,
+            (None, None) => true,
+            (None, Some(_)) => false,
+        }));
+
+        // fast.com consistently serves from CDN locations KNOWN_CITY_COORDINATES
+        // covers (major US/EU/APAC metros); if no target's location resolves,
+        // geocoding silently stopped working rather than this client simply
+        // being in an uncovered region.
+        assert!(
+            speed_test
+                .targets
+                .as_ref()
+                .unwrap()
+                .iter()
+                .any(|target| resolve_coordinates(&target.location).is_some()),
+            "expected at least one target to resolve coordinates via geocode"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn speed_test_measure_download_speed_works() -> SpeedTestResult<()> {
         let mut speed_test = SpeedTest::new(TOKEN);
@@ -189,15 +818,40 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn speed_test_measure_upload_speed_works() -> SpeedTestResult<()> {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        speed_test.set_upload_size(TEST_UPLOAD_SIZE);
+        let _target_upload_information = speed_test.measure_upload_speed().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn speed_test_measure_latency_works() -> SpeedTestResult<()> {
+        let mut speed_test = SpeedTest::new(TOKEN);
+        let latency_information = speed_test.measure_latency().await?;
+
+        assert!(latency_information.samples > 0);
+        assert!(latency_information.min_ms <= latency_information.max_ms);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn speed_test_add_hooks_works() -> SpeedTestResult<()> {
         let mut speed_test = SpeedTest::new(TOKEN);
+        speed_test.set_upload_size(TEST_UPLOAD_SIZE);
 
         #[derive(Debug, Default)]
         struct Events {
             on_download: bool,
             on_downloading: bool,
             on_downloaded: bool,
+            on_upload: bool,
+            on_uploading: bool,
+            on_uploaded: bool,
+            on_latency: bool,
         };
 
         impl SpeedTestEvents for Events {
@@ -212,16 +866,38 @@ mod tests {
             fn on_downloaded(&mut self, _target_download_information: &TargetDownloadInformation) {
                 self.on_downloaded = true;
             }
+
+            fn on_upload(&mut self, _target_upload_information: &TargetUploadInformation) {
+                self.on_upload = true;
+            }
+
+            fn on_uploading(&mut self, _target_upload_information: &TargetUploadInformation) {
+                self.on_uploading = true;
+            }
+
+            fn on_uploaded(&mut self, _target_upload_information: &TargetUploadInformation) {
+                self.on_uploaded = true;
+            }
+
+            fn on_latency(&mut self, _latency_information: &LatencyInformation) {
+                self.on_latency = true;
+            }
         }
 
         let events: Events = Default::default();
 
         let events = speed_test.add_events_hook(events);
         speed_test.measure_download_speed().await?;
+        speed_test.measure_upload_speed().await?;
+        speed_test.measure_latency().await?;
 
         assert_eq!(events.read().on_download, true);
         assert_eq!(events.read().on_downloading, true);
         assert_eq!(events.read().on_downloaded, true);
+        assert_eq!(events.read().on_upload, true);
+        assert_eq!(events.read().on_uploading, true);
+        assert_eq!(events.read().on_uploaded, true);
+        assert_eq!(events.read().on_latency, true);
 
         Ok(())
     }